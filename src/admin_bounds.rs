@@ -0,0 +1,90 @@
+use sqlx::{Pool, Row, Sqlite};
+use std::collections::HashMap;
+
+/// A province/regency/district's centroid and bounding box, derived by
+/// aggregating over its descendant villages -- only `villages` stores its
+/// own `lat`/`lng`/`min_lat`/`min_lng`/`max_lat`/`max_lng` (see
+/// `spatial_index.rs`), so these are computed, not read off the table.
+pub struct Bounds {
+    pub lat: f64,
+    pub lng: f64,
+    pub min_lat: f64,
+    pub min_lng: f64,
+    pub max_lat: f64,
+    pub max_lng: f64,
+}
+
+/// Centroid/bounding box lookup for every province, regency, and district,
+/// keyed by id. Built once at startup (like the village R-tree) so a
+/// forward-geocode request at these levels is a HashMap lookup rather than a
+/// live `AVG`/`MIN`/`MAX` aggregation over the whole hierarchy.
+pub struct AdminBounds {
+    pub provinces: HashMap<String, Bounds>,
+    pub regencies: HashMap<String, Bounds>,
+    pub districts: HashMap<String, Bounds>,
+}
+
+async fn load_bounds(db: &Pool<Sqlite>, query: &str) -> Result<HashMap<String, Bounds>, sqlx::Error> {
+    let rows = sqlx::query(query).fetch_all(db).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let id: String = row.get("id");
+            let bounds = Bounds {
+                lat: row.get("lat"),
+                lng: row.get("lng"),
+                min_lat: row.get("min_lat"),
+                min_lng: row.get("min_lng"),
+                max_lat: row.get("max_lat"),
+                max_lng: row.get("max_lng"),
+            };
+            (id, bounds)
+        })
+        .collect())
+}
+
+/// Runs one aggregation query per level at startup and caches the result,
+/// so forward-geocode requests never re-run these joins.
+pub async fn build_admin_bounds(db: &Pool<Sqlite>) -> Result<AdminBounds, sqlx::Error> {
+    let provinces = load_bounds(
+        db,
+        r#"
+        SELECT p.id as id, AVG(v.lat) as lat, AVG(v.lng) as lng,
+               MIN(v.min_lat) as min_lat, MIN(v.min_lng) as min_lng, MAX(v.max_lat) as max_lat, MAX(v.max_lng) as max_lng
+        FROM provinces p
+        JOIN regencies r ON r.parent_id = p.id
+        JOIN districts d ON d.parent_id = r.id
+        JOIN villages v ON v.parent_id = d.id
+        GROUP BY p.id
+        "#,
+    )
+    .await?;
+
+    let regencies = load_bounds(
+        db,
+        r#"
+        SELECT r.id as id, AVG(v.lat) as lat, AVG(v.lng) as lng,
+               MIN(v.min_lat) as min_lat, MIN(v.min_lng) as min_lng, MAX(v.max_lat) as max_lat, MAX(v.max_lng) as max_lng
+        FROM regencies r
+        JOIN districts d ON d.parent_id = r.id
+        JOIN villages v ON v.parent_id = d.id
+        GROUP BY r.id
+        "#,
+    )
+    .await?;
+
+    let districts = load_bounds(
+        db,
+        r#"
+        SELECT d.id as id, AVG(v.lat) as lat, AVG(v.lng) as lng,
+               MIN(v.min_lat) as min_lat, MIN(v.min_lng) as min_lng, MAX(v.max_lat) as max_lat, MAX(v.max_lng) as max_lng
+        FROM districts d
+        JOIN villages v ON v.parent_id = d.id
+        GROUP BY d.id
+        "#,
+    )
+    .await?;
+
+    Ok(AdminBounds { provinces, regencies, districts })
+}
@@ -0,0 +1,127 @@
+use serde::Deserialize;
+use std::net::SocketAddr;
+use std::path::Path;
+
+/// Top-level `config.toml` schema. Every section is optional in the file
+/// itself -- any section (or key) left out falls back to its default so a
+/// minimal or even empty config.toml is valid.
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub labels: LabelsConfig,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub listen_on: SocketAddr,
+    pub pool_size: u32,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            listen_on: "0.0.0.0:3000".parse().unwrap(),
+            pool_size: 100,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    pub path: String,
+    pub download_url: String,
+    pub auto_download: bool,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            path: "indonesia_area.db".to_string(),
+            download_url: "https://github.com/agusibrahim/indonesian-geocoder/releases/download/db/indonesia_area.db".to_string(),
+            auto_download: true,
+        }
+    }
+}
+
+/// Prefixes used when building a `full_name` like "Kelurahan X, Kecamatan Y,
+/// Z, W". Kept configurable so deployments outside Indonesia (or wanting a
+/// different language) can relabel without a code change.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct LabelsConfig {
+    pub village: String,
+    pub district: String,
+}
+
+impl Default for LabelsConfig {
+    fn default() -> Self {
+        Self {
+            village: "Kelurahan".to_string(),
+            district: "Kecamatan".to_string(),
+        }
+    }
+}
+
+impl Config {
+    /// Loads `config.toml` from `path` if it exists, otherwise falls back to
+    /// defaults that match the service's historical hardcoded behavior.
+    pub fn load(path: &str) -> anyhow::Result<Config> {
+        if Path::new(path).exists() {
+            let text = std::fs::read_to_string(path)?;
+            Ok(toml::from_str(&text)?)
+        } else {
+            Ok(Config::default())
+        }
+    }
+
+    pub fn full_name(&self, village: &str, district: &str, regency: &str, province: &str) -> String {
+        format!(
+            "{} {}, {} {}, {}, {}",
+            self.labels.village, village, self.labels.district, district, regency, province
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_missing_file_falls_back_to_defaults() {
+        let config = Config::load("does-not-exist.toml").unwrap();
+        assert_eq!(config.server.listen_on, "0.0.0.0:3000".parse().unwrap());
+        assert_eq!(config.server.pool_size, 100);
+        assert_eq!(config.database.path, "indonesia_area.db");
+        assert!(config.database.auto_download);
+        assert_eq!(config.labels.village, "Kelurahan");
+        assert_eq!(config.labels.district, "Kecamatan");
+    }
+
+    #[test]
+    fn partial_toml_keeps_defaults_for_omitted_sections() {
+        let config: Config = toml::from_str(
+            r#"
+            [labels]
+            village = "Desa"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.labels.village, "Desa");
+        assert_eq!(config.labels.district, "Kecamatan");
+        assert_eq!(config.server.pool_size, 100);
+    }
+
+    #[test]
+    fn full_name_uses_configured_labels() {
+        let config = Config::default();
+        assert_eq!(
+            config.full_name("Sukamaju", "Cicendo", "Bandung", "Jawa Barat"),
+            "Kelurahan Sukamaju, Kecamatan Cicendo, Bandung, Jawa Barat"
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use crate::LocationInfo;
+use axum::{http::header, response::IntoResponse};
+use geo::{Coord, Geometry, LineString, Polygon};
+use serde::Deserialize;
+use serde_json::json;
+
+/// Output format selected via the `format` query parameter on the geocode
+/// endpoints. `Json` keeps the existing `APIResponse<T>` envelope; `Geojson`
+/// and `Gpx` are meant for direct consumption by map/GPS tooling.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Geojson,
+    Gpx,
+}
+
+fn ring_coords(line: &LineString<f64>) -> serde_json::Value {
+    json!(line.coords().map(|c: &Coord<f64>| [c.x, c.y]).collect::<Vec<_>>())
+}
+
+fn polygon_coords(polygon: &Polygon<f64>) -> serde_json::Value {
+    let mut rings = vec![ring_coords(polygon.exterior())];
+    rings.extend(polygon.interiors().iter().map(ring_coords));
+    json!(rings)
+}
+
+/// Converts a `geo::Geometry` decoded from the `boundaries` WKB column into a
+/// GeoJSON geometry object. Village boundaries are (multi)polygons in
+/// practice; points and line strings are converted too, and any other
+/// geometry kind falls back to an empty GeometryCollection.
+fn geometry_to_geojson(geometry: &Geometry<f64>) -> serde_json::Value {
+    match geometry {
+        Geometry::Point(p) => json!({"type": "Point", "coordinates": [p.x(), p.y()]}),
+        Geometry::LineString(line) => json!({"type": "LineString", "coordinates": ring_coords(line)}),
+        Geometry::Polygon(polygon) => json!({"type": "Polygon", "coordinates": polygon_coords(polygon)}),
+        Geometry::MultiPolygon(polygons) => json!({
+            "type": "MultiPolygon",
+            "coordinates": polygons.iter().map(polygon_coords).collect::<Vec<_>>(),
+        }),
+        _ => json!({"type": "GeometryCollection", "geometries": []}),
+    }
+}
+
+/// Builds a GeoJSON `Feature` for a location. When `boundary` is supplied the
+/// full village polygon is used as the geometry, otherwise the centroid.
+pub fn location_feature(location: &LocationInfo, boundary: Option<&Geometry<f64>>) -> serde_json::Value {
+    let geometry = match boundary {
+        Some(geometry) => geometry_to_geojson(geometry),
+        None => json!({"type": "Point", "coordinates": [location.lng, location.lat]}),
+    };
+
+    json!({
+        "type": "Feature",
+        "geometry": geometry,
+        "properties": {
+            "level": location.level,
+            "id": location.id,
+            "full_name": location.full_name,
+            "location_detail": location.location_detail,
+            "bounding_box": location.bounding_box,
+        },
+    })
+}
+
+pub fn feature_collection(features: Vec<serde_json::Value>) -> serde_json::Value {
+    json!({"type": "FeatureCollection", "features": features})
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Renders a set of locations as a GPX document, one `<wpt>` per location
+/// with `<name>` set to `full_name`.
+pub fn gpx_document(locations: &[LocationInfo]) -> String {
+    let mut waypoints = String::new();
+    for location in locations {
+        waypoints.push_str(&format!(
+            "  <wpt lat=\"{}\" lon=\"{}\">\n    <name>{}</name>\n  </wpt>\n",
+            location.lat,
+            location.lng,
+            escape_xml(&location.full_name)
+        ));
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<gpx version=\"1.1\" creator=\"indonesian-geocoder\" xmlns=\"http://www.topografix.com/GPX/1/1\">\n{}</gpx>\n",
+        waypoints
+    )
+}
+
+pub fn geojson_response(value: serde_json::Value) -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "application/geo+json")], value.to_string())
+}
+
+pub fn gpx_response(document: String) -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "application/gpx+xml")], document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, LocationDetail};
+
+    fn sample_location() -> LocationInfo {
+        LocationInfo {
+            level: "village".to_string(),
+            id: "123".to_string(),
+            name: "Sukamaju".to_string(),
+            location_detail: LocationDetail {
+                province: "Jawa Barat".to_string(),
+                regency: "Bandung".to_string(),
+                district: "Cicendo".to_string(),
+                village: "Sukamaju".to_string(),
+            },
+            full_name: "Kelurahan Sukamaju, Kecamatan Cicendo, Bandung, Jawa Barat".to_string(),
+            lat: -6.9,
+            lng: 107.6,
+            distance_meters: None,
+            bounding_box: Some(BoundingBox {
+                min_lat: -7.0,
+                min_lng: 107.5,
+                max_lat: -6.8,
+                max_lng: 107.7,
+            }),
+        }
+    }
+
+    fn sample_polygon() -> Polygon<f64> {
+        let exterior = LineString::from(vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 0.0)]);
+        Polygon::new(exterior, vec![])
+    }
+
+    #[test]
+    fn escape_xml_escapes_reserved_characters() {
+        assert_eq!(escape_xml(r#"Tom & Jerry's <Shop>"#), "Tom &amp; Jerry's &lt;Shop&gt;");
+    }
+
+    #[test]
+    fn escape_xml_leaves_plain_text_untouched() {
+        assert_eq!(escape_xml("Jakarta Pusat"), "Jakarta Pusat");
+    }
+
+    #[test]
+    fn gpx_document_wraps_one_waypoint_per_location() {
+        let document = gpx_document(&[sample_location()]);
+
+        assert!(document.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+        assert!(document.contains("<wpt lat=\"-6.9\" lon=\"107.6\">"));
+        assert!(document.contains("<name>Kelurahan Sukamaju, Kecamatan Cicendo, Bandung, Jawa Barat</name>"));
+    }
+
+    #[test]
+    fn gpx_document_escapes_location_name() {
+        let mut location = sample_location();
+        location.full_name = "Tom & Jerry's".to_string();
+
+        let document = gpx_document(&[location]);
+        assert!(document.contains("<name>Tom &amp; Jerry's</name>"));
+    }
+
+    #[test]
+    fn geometry_to_geojson_point() {
+        let geometry = Geometry::Point(geo::Point::new(107.6, -6.9));
+        assert_eq!(geometry_to_geojson(&geometry), json!({"type": "Point", "coordinates": [107.6, -6.9]}));
+    }
+
+    #[test]
+    fn geometry_to_geojson_polygon_uses_exterior_ring() {
+        let value = geometry_to_geojson(&Geometry::Polygon(sample_polygon()));
+        assert_eq!(value["type"], "Polygon");
+        assert_eq!(value["coordinates"][0].as_array().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn location_feature_falls_back_to_centroid_point_without_boundary() {
+        let feature = location_feature(&sample_location(), None);
+        assert_eq!(feature["geometry"], json!({"type": "Point", "coordinates": [107.6, -6.9]}));
+        assert_eq!(feature["properties"]["id"], "123");
+    }
+
+    #[test]
+    fn location_feature_uses_boundary_geometry_when_given() {
+        let geometry = Geometry::Polygon(sample_polygon());
+        let feature = location_feature(&sample_location(), Some(&geometry));
+        assert_eq!(feature["geometry"]["type"], "Polygon");
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::HashSet;
+
+/// Character 3-gram set of `s`, used for a cheap Jaccard similarity score.
+/// Strings shorter than 3 characters fall back to the whole string as a
+/// single gram so short terms still compare sensibly against each other; an
+/// empty string yields an empty set.
+pub fn trigrams(s: &str) -> HashSet<String> {
+    let chars: Vec<char> = s.chars().collect();
+
+    if chars.len() < 3 {
+        return if chars.is_empty() {
+            HashSet::new()
+        } else {
+            HashSet::from([chars.into_iter().collect()])
+        };
+    }
+
+    chars.windows(3).map(|w| w.iter().collect()).collect()
+}
+
+/// Jaccard similarity between two trigram sets, in `[0.0, 1.0]`.
+pub fn trigram_similarity(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let intersection = a.intersection(b).count() as f64;
+    let union = a.union(b).count() as f64;
+    intersection / union
+}
+
+/// Levenshtein edit distance between `a` and `b`, bounded at `max_distance`:
+/// returns `None` as soon as it's clear the real distance exceeds the bound,
+/// so far-apart strings don't pay the full O(len_a * len_b) cost.
+pub fn bounded_levenshtein(a: &str, b: &str, max_distance: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    if a.len().abs_diff(b.len()) > max_distance {
+        return None;
+    }
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0usize; b.len() + 1];
+
+    for (i, &ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        let mut row_min = curr[0];
+
+        for (j, &cb) in b.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j + 1] + 1).min(curr[j] + 1).min(prev[j] + cost);
+            row_min = row_min.min(curr[j + 1]);
+        }
+
+        if row_min > max_distance {
+            return None;
+        }
+
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    let distance = prev[b.len()];
+    (distance <= max_distance).then_some(distance)
+}
+
+/// Edit distance normalized to `[0.0, 1.0]` by the longer string's length,
+/// bounded at `max_distance` (returns `1.0`, i.e. maximally dissimilar, once
+/// the bound is exceeded).
+pub fn normalized_distance(a: &str, b: &str, max_distance: usize) -> f64 {
+    let longest = a.chars().count().max(b.chars().count()).max(1);
+    match bounded_levenshtein(a, b, max_distance) {
+        Some(distance) => distance as f64 / longest as f64,
+        None => 1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trigrams_windows_three_chars() {
+        let grams = trigrams("jogja");
+        assert_eq!(grams, HashSet::from(["jog", "ogj", "gja"].map(String::from)));
+    }
+
+    #[test]
+    fn trigrams_short_string_falls_back_to_whole_string() {
+        assert_eq!(trigrams("jo"), HashSet::from(["jo".to_string()]));
+        assert_eq!(trigrams(""), HashSet::new());
+    }
+
+    #[test]
+    fn trigram_similarity_shares_grams_despite_first_letter_typo() {
+        let a = trigrams("jogjakarta");
+        let b = trigrams("yogyakarta");
+        let score = trigram_similarity(&a, &b);
+        assert!(score > 0.0, "expected shared trigrams (e.g. \"kar\", \"art\"), got score {}", score);
+    }
+
+    #[test]
+    fn trigram_similarity_identical_sets_is_one() {
+        let a = trigrams("bandung");
+        assert_eq!(trigram_similarity(&a, &a), 1.0);
+    }
+
+    #[test]
+    fn trigram_similarity_empty_set_is_zero() {
+        let a = trigrams("bandung");
+        let empty = HashSet::new();
+        assert_eq!(trigram_similarity(&a, &empty), 0.0);
+    }
+
+    #[test]
+    fn bounded_levenshtein_counts_edits() {
+        assert_eq!(bounded_levenshtein("kitten", "sitting", 8), Some(3));
+        assert_eq!(bounded_levenshtein("jakarta", "jakarta", 8), Some(0));
+    }
+
+    #[test]
+    fn bounded_levenshtein_bails_out_past_max_distance() {
+        assert_eq!(bounded_levenshtein("jakarta", "completely different", 3), None);
+    }
+
+    #[test]
+    fn normalized_distance_is_fraction_of_longest_length() {
+        assert_eq!(normalized_distance("jakarta", "jakarta", 8), 0.0);
+        assert_eq!(normalized_distance("abc", "abd", 8), 1.0 / 3.0);
+    }
+
+    #[test]
+    fn normalized_distance_maxes_out_past_bound() {
+        assert_eq!(normalized_distance("jakarta", "completely different", 3), 1.0);
+    }
+}
@@ -1,8 +1,8 @@
 use axum::{
-    extract::{Query, State},
+    extract::{Path as AxumPath, Query, State},
     http::StatusCode,
     response::IntoResponse,
-    routing::get,
+    routing::{get, post},
     Json, Router,
 };
 use futures_util::StreamExt;
@@ -12,16 +12,47 @@ use geozero::{wkb::Wkb, ToGeo};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use sqlx::{sqlite::{SqliteConnectOptions, SqlitePoolOptions}, Pool, Row, Sqlite};
-use std::{env, io::Write, path::Path, sync::Arc};
+use std::{collections::HashMap, io::Write, path::Path, sync::Arc};
 use tower_http::cors::CorsLayer;
 use tracing::info;
 
-const DB_NAME: &str = "indonesia_area.db";
-const DB_DOWNLOAD_URL: &str = "https://github.com/agusibrahim/indonesian-geocoder/releases/download/db/indonesia_area.db";
+mod admin_bounds;
+mod config;
+mod formats;
+mod fuzzy;
+mod spatial_index;
+use admin_bounds::AdminBounds;
+use config::Config;
+use formats::OutputFormat;
+use spatial_index::VillageIndex;
+
+const CONFIG_PATH: &str = "config.toml";
+
+// Maximum number of per-point lookups that run concurrently against the pool
+// for a single batch request.
+const BATCH_CONCURRENCY: usize = 16;
+
+// Bound on the Levenshtein edit distance computed during fuzzy search, so a
+// query against a wildly different candidate name bails out early instead of
+// paying the full O(len_a * len_b) cost.
+const FUZZY_MAX_EDIT_DISTANCE: usize = 8;
+
+// Per-level candidate-superset cap for fuzzy search. Matches
+// `ADMIN_LEVEL_ROW_CAP` (the exact-match search's per-level cap) since both
+// bound the same per-level UNION ALL branch before in-memory ranking.
+const FUZZY_LEVEL_ROW_CAP: usize = ADMIN_LEVEL_ROW_CAP;
+
+// Bounded LRU cache of decoded village boundaries, keyed by village id, so
+// hot areas skip re-parsing the `boundaries` WKB column on every request.
+const GEOMETRY_CACHE_CAPACITY: u64 = 2000;
 
 #[derive(Clone)]
 struct AppState {
     db: Pool<Sqlite>,
+    config: Config,
+    village_index: Arc<VillageIndex>,
+    admin_bounds: Arc<AdminBounds>,
+    geometry_cache: moka::future::Cache<String, Arc<geo::Geometry<f64>>>,
 }
 
 // Request Models
@@ -29,6 +60,8 @@ struct AppState {
 struct ReverseGeocodeQuery {
     lat: f64,
     lng: f64,
+    #[serde(default)]
+    format: OutputFormat,
 }
 
 #[derive(Deserialize)]
@@ -38,6 +71,29 @@ struct SearchQuery {
     limit: usize,
     lat: Option<f64>,
     lng: Option<f64>,
+    #[serde(default)]
+    format: OutputFormat,
+    // Typo-tolerant trigram/edit-distance ranking instead of requiring
+    // substring matches on every keyword.
+    #[serde(default)]
+    fuzzy: bool,
+    #[serde(default = "default_min_score")]
+    min_score: f64,
+    // Comma-separated subset of "province,regency,district,village" to
+    // restrict which administrative levels are searched. Defaults to all.
+    levels: Option<String>,
+}
+
+fn default_min_score() -> f64 {
+    0.2
+}
+
+#[derive(Deserialize)]
+struct BatchReverseGeocodePoint {
+    lat: f64,
+    lng: f64,
+    #[serde(default)]
+    id: Option<String>,
 }
 
 // Response Models
@@ -66,6 +122,19 @@ struct LocationInfo {
 
     // Jarak dari titik input (dalam meter, tipe integer)
     distance_meters: Option<i64>,
+
+    // Populated for forward-geocode candidates so clients can zoom a map to
+    // fit the matched administrative entity.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bounding_box: Option<BoundingBox>,
+}
+
+#[derive(Serialize, Clone)]
+struct BoundingBox {
+    min_lat: f64,
+    min_lng: f64,
+    max_lat: f64,
+    max_lng: f64,
 }
 
 #[derive(Serialize)]
@@ -75,67 +144,100 @@ struct APIResponse<T> {
     error: Option<String>,
 }
 
+#[derive(Serialize)]
+struct BatchReverseGeocodeResult {
+    // Echoes back the client-supplied id (if any) so the response array can
+    // be matched up to the request array without relying solely on order.
+    id: Option<String>,
+    #[serde(flatten)]
+    result: APIResponse<LocationInfo>,
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Set default RUST_LOG ke info jika user tidak mensetnya
-    if env::var("RUST_LOG").is_err() {
-        env::set_var("RUST_LOG", "info");
+    if std::env::var("RUST_LOG").is_err() {
+        std::env::set_var("RUST_LOG", "info");
     }
     tracing_subscriber::fmt::init();
 
-    if !Path::new(DB_NAME).exists() {
-        info!("Database {} not found. Downloading...", DB_NAME);
-        download_database().await?;
+    let config = Config::load(CONFIG_PATH)?;
+
+    if !Path::new(&config.database.path).exists() {
+        if config.database.auto_download {
+            info!("Database {} not found. Downloading...", config.database.path);
+            download_database(&config.database).await?;
+        } else {
+            info!("Database {} not found and auto-download is disabled.", config.database.path);
+        }
     } else {
-        info!("Database {} found.", DB_NAME);
+        info!("Database {} found.", config.database.path);
     }
 
     let db_options = SqliteConnectOptions::new()
-        .filename(DB_NAME)
+        .filename(&config.database.path)
         .read_only(true);
 
     let pool = SqlitePoolOptions::new()
-        .max_connections(100)
+        .max_connections(config.server.pool_size)
         .connect_with(db_options)
         .await?;
 
-    let state = Arc::new(AppState { db: pool });
+    info!("Building village bounding-box index...");
+    let village_index = Arc::new(spatial_index::build_village_index(&pool).await?);
+    info!("Village index ready ({} villages).", village_index.size());
+
+    info!("Precomputing province/regency/district centroids and bounding boxes...");
+    let admin_bounds = Arc::new(admin_bounds::build_admin_bounds(&pool).await?);
+
+    let geometry_cache = moka::future::Cache::builder()
+        .max_capacity(GEOMETRY_CACHE_CAPACITY)
+        .build();
+
+    let listen_on = config.server.listen_on;
+    let state = Arc::new(AppState {
+        db: pool,
+        config,
+        village_index,
+        admin_bounds,
+        geometry_cache,
+    });
 
     let app = Router::new()
         .route("/api/v1/geocode/reverse", get(reverse_geocode))
+        .route("/api/v1/geocode/reverse/:lat/:lng", get(reverse_geocode_path))
+        .route("/api/v1/geocode/reverse/batch", post(reverse_geocode_batch))
         .route("/api/v1/places/search", get(search_places))
+        .route("/api/v1/places/search/:query", get(search_places_path))
         .layer(CorsLayer::permissive())
         .with_state(state);
 
-    let port = env::var("PORT").unwrap_or_else(|_| "3000".to_string());
-    let addr = format!("0.0.0.0:{}", port);
-
-    info!("🚀 Server running on http://{}", addr);
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    info!("🚀 Server running on http://{}", listen_on);
+    let listener = tokio::net::TcpListener::bind(listen_on).await?;
     axum::serve(listener, app).await?;
 
     Ok(())
 }
 
-async fn download_database() -> anyhow::Result<()> {
-    info!("Downloading database from {}...", DB_DOWNLOAD_URL);
+async fn download_database(database: &config::DatabaseConfig) -> anyhow::Result<()> {
+    info!("Downloading database from {}...", database.download_url);
 
     // Create client that follows redirects
     let client = Client::builder()
         .redirect(reqwest::redirect::Policy::limited(10))
         .build()?;
 
-    let response = client.get(DB_DOWNLOAD_URL).send().await?;
+    let response = client.get(&database.download_url).send().await?;
 
     if !response.status().is_success() {
-        tracing::warn!("Failed to download database (Status: {}). Make sure {} exists.", response.status(), DB_NAME);
+        tracing::warn!("Failed to download database (Status: {}). Make sure {} exists.", response.status(), database.path);
         return Ok(());
     }
 
     let total_size = response.content_length().unwrap_or(0);
     info!("Starting download... (Total size: {} bytes)", total_size);
 
-    let mut file = std::fs::File::create(DB_NAME)?;
+    let mut file = std::fs::File::create(&database.path)?;
     let mut downloaded: u64 = 0;
     let mut stream = response.bytes_stream();
 
@@ -160,15 +262,22 @@ async fn download_database() -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn reverse_geocode(
-    State(state): State<Arc<AppState>>,
-    Query(params): Query<ReverseGeocodeQuery>,
-) -> impl IntoResponse {
-    let lat = params.lat;
-    let lng = params.lng;
+// Resolves a single coordinate to the village containing it, shared by the
+// single-point and batch reverse-geocode endpoints. The R-tree in
+// `AppState::village_index` narrows the search to a handful of candidate
+// villages; only those rows are fetched from the database, and decoded
+// boundaries are cached so hot areas skip re-parsing WKB on every request.
+async fn lookup_village_at(state: &AppState, lat: f64, lng: f64) -> Result<Option<(LocationInfo, Arc<geo::Geometry<f64>>)>, sqlx::Error> {
     let user_point = Point::new(lng, lat);
 
-    let query = r#"
+    let candidate_ids = spatial_index::candidates_containing(&state.village_index, lat, lng);
+    if candidate_ids.is_empty() {
+        return Ok(None);
+    }
+
+    let placeholders = candidate_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+    let query = format!(
+        r#"
         SELECT v.id, v.name as village_name,
                d.name as district_name, r.name as regency_name, p.name as province_name,
                v.lat, v.lng, v.boundaries
@@ -176,59 +285,92 @@ async fn reverse_geocode(
         LEFT JOIN districts d ON v.parent_id = d.id
         LEFT JOIN regencies r ON d.parent_id = r.id
         LEFT JOIN provinces p ON r.parent_id = p.id
-        WHERE ? BETWEEN v.min_lat AND v.max_lat
-          AND ? BETWEEN v.min_lng AND v.max_lng
-    "#;
+        WHERE v.id IN ({})
+        "#,
+        placeholders
+    );
 
-    match sqlx::query(query).bind(lat).bind(lng).fetch_all(&state.db).await {
-        Ok(rows) => {
-            for row in rows {
+    let mut query_builder = sqlx::query(&query);
+    for id in &candidate_ids {
+        query_builder = query_builder.bind(id);
+    }
+
+    let rows = query_builder.fetch_all(&state.db).await?;
+
+    for row in rows {
+        let id: String = row.get("id");
+
+        let geom = match state.geometry_cache.get(&id).await {
+            Some(geom) => geom,
+            None => {
                 let wkb_data: Vec<u8> = row.get("boundaries");
-                let wkb_geom = Wkb(wkb_data);
-
-                if let Ok(geom) = wkb_geom.to_geo() {
-                    if geom.contains(&user_point) {
-                        let id: String = row.get("id");
-                        let v_name: String = row.get("village_name");
-                        let d_name: String = row.get("district_name");
-                        let r_name: String = row.get("regency_name");
-                        let p_name: String = row.get("province_name");
-                        let centroid_lat: f64 = row.get("lat");
-                        let centroid_lng: f64 = row.get("lng");
-
-                        let full_name = format!("Kelurahan {}, Kecamatan {}, {}, {}", v_name, d_name, r_name, p_name);
-
-                        let center_point = Point::new(centroid_lng, centroid_lat);
-                        let distance = user_point.haversine_distance(&center_point);
-
-                        return Json(APIResponse {
-                            success: true,
-                            data: Some(LocationInfo {
-                                level: "village".to_string(),
-                                id,
-                                name: v_name.clone(),
-                                location_detail: LocationDetail {
-                                    province: p_name,
-                                    regency: r_name,
-                                    district: d_name,
-                                    village: v_name,
-                                },
-                                full_name,
-                                lat: centroid_lat,
-                                lng: centroid_lng,
-                                distance_meters: Some(distance.round() as i64),
-                            }),
-                            error: None,
-                        }).into_response();
+                match Wkb(wkb_data).to_geo() {
+                    Ok(geom) => {
+                        let geom = Arc::new(geom);
+                        state.geometry_cache.insert(id.clone(), geom.clone()).await;
+                        geom
                     }
+                    Err(_) => continue,
                 }
             }
+        };
+
+        if geom.contains(&user_point) {
+            let v_name: String = row.get("village_name");
+            let d_name: String = row.get("district_name");
+            let r_name: String = row.get("regency_name");
+            let p_name: String = row.get("province_name");
+            let centroid_lat: f64 = row.get("lat");
+            let centroid_lng: f64 = row.get("lng");
+
+            let full_name = state.config.full_name(&v_name, &d_name, &r_name, &p_name);
+
+            let center_point = Point::new(centroid_lng, centroid_lat);
+            let distance = user_point.haversine_distance(&center_point);
+
+            let location = LocationInfo {
+                level: "village".to_string(),
+                id,
+                name: v_name.clone(),
+                location_detail: LocationDetail {
+                    province: p_name,
+                    regency: r_name,
+                    district: d_name,
+                    village: v_name,
+                },
+                full_name,
+                lat: centroid_lat,
+                lng: centroid_lng,
+                distance_meters: Some(distance.round() as i64),
+                bounding_box: None,
+            };
 
-            Json(APIResponse::<LocationInfo> {
-                success: false, data: None,
-                error: Some("Location not found".to_string()),
-            }).into_response()
+            return Ok(Some((location, geom)));
+        }
+    }
+
+    Ok(None)
+}
+
+// Shared by the query-string and path-parameter reverse-geocode routes.
+async fn respond_reverse_geocode(state: &AppState, lat: f64, lng: f64, format: OutputFormat) -> axum::response::Response {
+    match lookup_village_at(state, lat, lng).await {
+        Ok(Some((location, boundary))) => match format {
+            OutputFormat::Json => Json(APIResponse {
+                success: true,
+                data: Some(location),
+                error: None,
+            }).into_response(),
+            OutputFormat::Geojson => {
+                let feature = formats::location_feature(&location, Some(boundary.as_ref()));
+                formats::geojson_response(formats::feature_collection(vec![feature])).into_response()
+            }
+            OutputFormat::Gpx => formats::gpx_response(formats::gpx_document(&[location])).into_response(),
         },
+        Ok(None) => Json(APIResponse::<LocationInfo> {
+            success: false, data: None,
+            error: Some("Location not found".to_string()),
+        }).into_response(),
         Err(e) => {
             tracing::error!("Database error: {}", e);
             (StatusCode::INTERNAL_SERVER_ERROR, Json(APIResponse::<LocationInfo> {
@@ -238,70 +380,517 @@ async fn reverse_geocode(
     }
 }
 
-async fn search_places(
+async fn reverse_geocode(
     State(state): State<Arc<AppState>>,
-    Query(params): Query<SearchQuery>,
+    Query(params): Query<ReverseGeocodeQuery>,
 ) -> impl IntoResponse {
-    let limit = if params.limit == 0 { 10 } else { params.limit.min(50) };
+    respond_reverse_geocode(&state, params.lat, params.lng, params.format).await
+}
 
-    let keywords: Vec<String> = params.q
-        .to_lowercase()
-        .split_whitespace()
-        .map(|s| format!("%{}%", s))
-        .collect();
+// `GET /api/v1/geocode/reverse/:lat/:lng` - curl/shell-friendly alternative
+// to the query-string form, always returning the default JSON envelope.
+async fn reverse_geocode_path(
+    State(state): State<Arc<AppState>>,
+    AxumPath((lat, lng)): AxumPath<(f64, f64)>,
+) -> impl IntoResponse {
+    respond_reverse_geocode(&state, lat, lng, OutputFormat::Json).await
+}
+
+async fn reverse_geocode_batch(
+    State(state): State<Arc<AppState>>,
+    Json(points): Json<Vec<BatchReverseGeocodePoint>>,
+) -> impl IntoResponse {
+    // Dedupe identical coordinates so a GPS track with repeated/stationary
+    // points only hits the database once per distinct location.
+    let mut unique_coords: HashMap<(u64, u64), (f64, f64)> = HashMap::new();
+    for point in &points {
+        unique_coords.entry((point.lat.to_bits(), point.lng.to_bits())).or_insert((point.lat, point.lng));
+    }
+
+    let lookups: HashMap<(u64, u64), Result<Option<(LocationInfo, Arc<geo::Geometry<f64>>)>, sqlx::Error>> =
+        futures_util::stream::iter(unique_coords.into_iter())
+            .map(|(key, (lat, lng))| {
+                let state = state.clone();
+                async move { (key, lookup_village_at(&state, lat, lng).await) }
+            })
+            .buffer_unordered(BATCH_CONCURRENCY)
+            .collect::<HashMap<_, _>>()
+            .await;
+
+    let results: Vec<BatchReverseGeocodeResult> = points.into_iter().map(|point| {
+        let key = (point.lat.to_bits(), point.lng.to_bits());
+        let result = match lookups.get(&key) {
+            Some(Ok(Some((location, _boundary)))) => APIResponse {
+                success: true,
+                data: Some(location.clone()),
+                error: None,
+            },
+            Some(Ok(None)) => APIResponse {
+                success: false, data: None,
+                error: Some("Location not found".to_string()),
+            },
+            Some(Err(e)) => {
+                tracing::error!("Database error: {}", e);
+                APIResponse {
+                    success: false, data: None,
+                    error: Some("Internal server error".to_string()),
+                }
+            }
+            None => APIResponse {
+                success: false, data: None,
+                error: Some("Internal server error".to_string()),
+            },
+        };
+
+        BatchReverseGeocodeResult { id: point.id, result }
+    }).collect();
 
-    if keywords.is_empty() {
-        return Json(APIResponse {
+    Json(results).into_response()
+}
+
+// Renders a list of locations in the requested output format. Used by
+// `search_places`, whose results are always centroid-only (no boundary).
+fn render_locations(locations: Vec<LocationInfo>, format: OutputFormat) -> impl IntoResponse {
+    match format {
+        OutputFormat::Json => Json(APIResponse {
             success: true,
-            data: Some(Vec::<LocationInfo>::new()),
+            data: Some(locations),
             error: None,
-        }).into_response();
+        }).into_response(),
+        OutputFormat::Geojson => {
+            let features = locations.iter().map(|l| formats::location_feature(l, None)).collect();
+            formats::geojson_response(formats::feature_collection(features)).into_response()
+        }
+        OutputFormat::Gpx => formats::gpx_response(formats::gpx_document(&locations)).into_response(),
     }
+}
 
-    let mut where_clauses = Vec::new();
-    for _ in 0..keywords.len() {
-        where_clauses.push("(LOWER(v.name) LIKE ? OR LOWER(d.name) LIKE ? OR LOWER(r.name) LIKE ? OR LOWER(p.name) LIKE ?)");
+// The four administrative levels a forward-geocode search can match,
+// ordered from broadest to narrowest. Used both to validate `levels=` and
+// to rank matches (a match on a broader level outranks a deeper one).
+const ADMIN_LEVELS: [&str; 4] = ["province", "regency", "district", "village"];
+
+// Per-row cap for each level's branch of the forward-geocode UNION ALL,
+// mirroring the `LIMIT 100` the old village-only search applied before
+// ranking and truncating to the caller's `limit` -- without it a broad
+// query (e.g. a single letter) would pull every matching row, across four
+// tables and their joins, into memory before we get to rank anything.
+const ADMIN_LEVEL_ROW_CAP: usize = 100;
+
+// Only `villages` stores its own centroid (`lat`/`lng`) and decoded boundary
+// bounding box (`min_lat`/`min_lng`/`max_lat`/`max_lng`) -- see
+// `spatial_index.rs`, which reads exactly those columns to build the R-tree.
+// `provinces`/`regencies`/`districts` carry no such columns, so a
+// province/regency/district candidate's centroid and bounding box come from
+// `AdminBounds`, precomputed once at startup by `admin_bounds.rs` in exactly
+// the same way the village R-tree is: these branches themselves select NULL
+// for those columns and just filter/rank by name, so `LIMIT {cap}` bounds a
+// plain row scan rather than an aggregation over the whole hierarchy.
+//
+// Per-level SELECT used to build the UNION ALL forward-geocode query. Every
+// branch exposes the same column shape (level, id, name, parent1, parent2,
+// parent3, lat, lng, bounding box) so the results can be read uniformly;
+// parent columns beyond an entity's own level, and all of lat/lng/bounding
+// box for non-village levels, are NULL -- callers resolve those via
+// `resolve_bounds`. `{where}` is filled in by the caller, since the
+// exact and fuzzy search modes filter differently (tokenized substrings
+// vs. a trigram superset).
+fn admin_level_template(level: &str) -> &'static str {
+    match level {
+        "province" => r#"
+            SELECT 'province' as level, p.id, p.name as name, NULL as parent1, NULL as parent2, NULL as parent3,
+                   NULL as lat, NULL as lng, NULL as min_lat, NULL as min_lng, NULL as max_lat, NULL as max_lng
+            FROM provinces p
+            WHERE {where}
+            LIMIT {cap}
+        "#,
+        "regency" => r#"
+            SELECT 'regency' as level, r.id, r.name as name, p.name as parent1, NULL as parent2, NULL as parent3,
+                   NULL as lat, NULL as lng, NULL as min_lat, NULL as min_lng, NULL as max_lat, NULL as max_lng
+            FROM regencies r
+            LEFT JOIN provinces p ON r.parent_id = p.id
+            WHERE {where}
+            LIMIT {cap}
+        "#,
+        "district" => r#"
+            SELECT 'district' as level, d.id, d.name as name, r.name as parent1, p.name as parent2, NULL as parent3,
+                   NULL as lat, NULL as lng, NULL as min_lat, NULL as min_lng, NULL as max_lat, NULL as max_lng
+            FROM districts d
+            LEFT JOIN regencies r ON d.parent_id = r.id
+            LEFT JOIN provinces p ON r.parent_id = p.id
+            WHERE {where}
+            LIMIT {cap}
+        "#,
+        _ => r#"
+            SELECT 'village' as level, v.id, v.name as name, d.name as parent1, r.name as parent2, p.name as parent3,
+                   v.lat, v.lng, v.min_lat, v.min_lng, v.max_lat, v.max_lng
+            FROM villages v
+            LEFT JOIN districts d ON v.parent_id = d.id
+            LEFT JOIN regencies r ON d.parent_id = r.id
+            LEFT JOIN provinces p ON r.parent_id = p.id
+            WHERE {where}
+            LIMIT {cap}
+        "#,
     }
+}
 
-    let where_sql = where_clauses.join(" AND ");
+// Resolves a forward-geocode candidate's centroid and bounding box. Village
+// rows carry their own columns straight off `villages` (read by the caller);
+// every other level looks its id up in the `AdminBounds` precomputed at
+// startup, since `admin_level_template` selects NULL for those columns on
+// non-village branches.
+fn resolve_bounds(admin_bounds: &AdminBounds, level: &str, id: &str) -> (f64, f64, Option<BoundingBox>) {
+    let bounds = match level {
+        "province" => admin_bounds.provinces.get(id),
+        "regency" => admin_bounds.regencies.get(id),
+        "district" => admin_bounds.districts.get(id),
+        _ => None,
+    };
+
+    match bounds {
+        Some(b) => (
+            b.lat,
+            b.lng,
+            Some(BoundingBox {
+                min_lat: b.min_lat,
+                min_lng: b.min_lng,
+                max_lat: b.max_lat,
+                max_lng: b.max_lng,
+            }),
+        ),
+        None => (0.0, 0.0, None),
+    }
+}
 
-    let query_str = format!(
-        r#"
-        SELECT 'village' as level, v.id, v.name as v_name, d.name as d_name, r.name as r_name, p.name as p_name, v.lat, v.lng
-        FROM villages v
-        LEFT JOIN districts d ON v.parent_id = d.id
-        LEFT JOIN regencies r ON d.parent_id = r.id
-        LEFT JOIN provinces p ON r.parent_id = p.id
-        WHERE {}
-        LIMIT 100
-        "#,
-        where_sql
-    );
+// Name columns a `levels`-selected branch can match against: the entity's
+// own name plus whichever ancestor names it joins in. Shared by the
+// exact search (AND-of-tokens over these columns) and fuzzy search
+// (OR-of-trigrams over these columns) so both modes search the same surface
+// per level.
+fn name_columns_for_level(level: &str) -> &'static [&'static str] {
+    match level {
+        "province" => &["p.name"],
+        "regency" => &["r.name", "p.name"],
+        "district" => &["d.name", "r.name", "p.name"],
+        _ => &["v.name", "d.name", "r.name", "p.name"],
+    }
+}
 
-    let mut query_builder = sqlx::query(&query_str);
+// Fills in a level's `{where}` clause and row cap, producing a standalone
+// SELECT ready to be wrapped in parens and joined with `UNION ALL`.
+fn admin_level_select(level: &str, where_clause: &str, cap: usize) -> String {
+    admin_level_template(level)
+        .replacen("{where}", where_clause, 1)
+        .replacen("{cap}", &cap.to_string(), 1)
+}
+
+fn selected_levels(levels: Option<&str>) -> Vec<&'static str> {
+    let requested: Option<Vec<&str>> = levels.map(|s| s.split(',').map(str::trim).collect());
+
+    ADMIN_LEVELS
+        .into_iter()
+        .filter(|level| match &requested {
+            Some(requested) => requested.contains(level),
+            None => true,
+        })
+        .collect()
+}
 
-    for kw in &keywords {
-        query_builder = query_builder.bind(kw).bind(kw).bind(kw).bind(kw);
+fn location_detail_for_level(level: &str, name: &str, parent1: &Option<String>, parent2: &Option<String>, parent3: &Option<String>) -> LocationDetail {
+    match level {
+        "province" => LocationDetail {
+            province: name.to_string(),
+            regency: String::new(),
+            district: String::new(),
+            village: String::new(),
+        },
+        "regency" => LocationDetail {
+            province: parent1.clone().unwrap_or_default(),
+            regency: name.to_string(),
+            district: String::new(),
+            village: String::new(),
+        },
+        "district" => LocationDetail {
+            province: parent2.clone().unwrap_or_default(),
+            regency: parent1.clone().unwrap_or_default(),
+            district: name.to_string(),
+            village: String::new(),
+        },
+        _ => LocationDetail {
+            province: parent3.clone().unwrap_or_default(),
+            regency: parent2.clone().unwrap_or_default(),
+            district: parent1.clone().unwrap_or_default(),
+            village: name.to_string(),
+        },
+    }
+}
+
+// Builds the `full_name`/`formatted_address` for a forward-geocode
+// candidate, honoring the configured label prefixes at village/district
+// level the same way `reverse_geocode` does.
+fn full_name_for_level(config: &Config, level: &str, detail: &LocationDetail) -> String {
+    match level {
+        "province" => detail.province.clone(),
+        "regency" => format!("{}, {}", detail.regency, detail.province),
+        "district" => format!("{} {}, {}, {}", config.labels.district, detail.district, detail.regency, detail.province),
+        _ => config.full_name(&detail.village, &detail.district, &detail.regency, &detail.province),
+    }
+}
+
+// Shared by the query-string and path-parameter search routes. Searches the
+// full administrative hierarchy (province/regency/district/village) rather
+// than villages alone, ranking exact and broader-level matches above deep
+// partial village matches.
+#[allow(clippy::too_many_arguments)]
+async fn do_search(state: &AppState, q: &str, limit: usize, lat: Option<f64>, lng: Option<f64>, format: OutputFormat, fuzzy: bool, min_score: f64, levels: Option<&str>) -> axum::response::Response {
+    if fuzzy {
+        return do_fuzzy_search(state, q, limit, lat, lng, format, min_score, levels).await;
+    }
+
+    let limit = if limit == 0 { 10 } else { limit.min(50) };
+
+    let query_lower = q.to_lowercase();
+    let query_trimmed = query_lower.trim();
+    if query_trimmed.is_empty() {
+        return render_locations(Vec::new(), format).into_response();
+    }
+
+    // One token per word, ANDed together, each matched against the level's
+    // own name OR any of its ancestor names -- e.g. "jakarta pusat" matches
+    // a district named "pusat" whose ancestor province is "jakarta", same as
+    // the pre-forward-geocode village-only search matched across columns.
+    let keywords: Vec<String> = query_trimmed.split_whitespace().map(|w| format!("%{}%", w)).collect();
+
+    let levels = selected_levels(levels);
+    if levels.is_empty() {
+        return render_locations(Vec::new(), format).into_response();
+    }
+
+    let query_str = levels
+        .iter()
+        .map(|level| {
+            let columns = name_columns_for_level(level);
+            let where_clause = keywords
+                .iter()
+                .map(|_| format!("({})", columns.iter().map(|c| format!("LOWER({}) LIKE ?", c)).collect::<Vec<_>>().join(" OR ")))
+                .collect::<Vec<_>>()
+                .join(" AND ");
+            format!("({})", admin_level_select(level, &where_clause, ADMIN_LEVEL_ROW_CAP))
+        })
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ");
+
+    let mut query_builder = sqlx::query(&query_str);
+    for level in &levels {
+        let columns = name_columns_for_level(level);
+        for keyword in &keywords {
+            for _ in columns {
+                query_builder = query_builder.bind(keyword);
+            }
+        }
     }
 
     match query_builder.fetch_all(&state.db).await {
         Ok(rows) => {
-            let mut results = Vec::new();
+            let user_loc = match (lat, lng) {
+                (Some(lat), Some(lng)) => Some(Point::new(lng, lat)),
+                _ => None,
+            };
+
+            // (exact match first, broader level first, closer distance first, shorter name first)
+            let mut ranked: Vec<(bool, usize, i64, usize, LocationInfo)> = Vec::new();
+
+            for row in rows {
+                let level: String = row.get("level");
+                let id: String = row.get("id");
+                let name: String = row.get("name");
+                let parent1: Option<String> = row.get("parent1");
+                let parent2: Option<String> = row.get("parent2");
+                let parent3: Option<String> = row.get("parent3");
+
+                let (centroid_lat, centroid_lng, bounding_box) = if level == "village" {
+                    (
+                        row.get("lat"),
+                        row.get("lng"),
+                        Some(BoundingBox {
+                            min_lat: row.get("min_lat"),
+                            min_lng: row.get("min_lng"),
+                            max_lat: row.get("max_lat"),
+                            max_lng: row.get("max_lng"),
+                        }),
+                    )
+                } else {
+                    resolve_bounds(&state.admin_bounds, &level, &id)
+                };
+
+                let location_detail = location_detail_for_level(&level, &name, &parent1, &parent2, &parent3);
+                let full_name = full_name_for_level(&state.config, &level, &location_detail);
+
+                let mut dist_meters = None;
+                if let Some(user_pt) = user_loc {
+                    let loc_pt = Point::new(centroid_lng, centroid_lat);
+                    dist_meters = Some(user_pt.haversine_distance(&loc_pt).round() as i64);
+                }
+
+                let is_exact = name.to_lowercase() == query_trimmed;
+                let level_rank = ADMIN_LEVELS.iter().position(|l| *l == level).unwrap_or(ADMIN_LEVELS.len());
+
+                let location = LocationInfo {
+                    level,
+                    id,
+                    name,
+                    location_detail,
+                    full_name,
+                    lat: centroid_lat,
+                    lng: centroid_lng,
+                    distance_meters: dist_meters,
+                    bounding_box,
+                };
+
+                ranked.push((is_exact, level_rank, dist_meters.unwrap_or(i64::MAX), location.name.len(), location));
+            }
+
+            ranked.sort_by(|a, b| {
+                b.0.cmp(&a.0)
+                    .then(a.1.cmp(&b.1))
+                    .then(a.2.cmp(&b.2))
+                    .then(a.3.cmp(&b.3))
+            });
+
+            let results: Vec<LocationInfo> = ranked.into_iter().take(limit).map(|(_, _, _, _, location)| location).collect();
+
+            render_locations(results, format).into_response()
+        },
+        Err(e) => {
+            tracing::error!("Database error: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(APIResponse::<Vec<LocationInfo>> {
+                    success: false,
+                    data: None,
+                    error: Some("Internal server error".to_string()),
+                })
+            ).into_response()
+        }
+    }
+}
+
+// Builds the trigram-superset `WHERE` clause and its bind values for one
+// level's branch: a row matches if ANY trigram of ANY query token appears in
+// ANY of that level's name columns. This is deliberately looser than a
+// prefix match -- a first-letter typo like "Jogjakarta" still shares
+// trigrams ("kar", "art", "rta", ...) with "Yogyakarta", so the correct row
+// reaches the in-memory ranking stage instead of being filtered out before
+// similarity is ever computed.
+fn fuzzy_level_where(columns: &[&str], token_trigrams: &[Vec<String>]) -> (String, Vec<String>) {
+    let mut clauses = Vec::new();
+    let mut binds = Vec::new();
+
+    for trigrams in token_trigrams {
+        for trigram in trigrams {
+            let pattern = format!("%{}%", trigram);
+            for column in columns {
+                clauses.push(format!("LOWER({}) LIKE ?", column));
+                binds.push(pattern.clone());
+            }
+        }
+    }
+
+    (clauses.join(" OR "), binds)
+}
+
+// Typo-tolerant search: broadens the SQL filter to a trigram superset of
+// likely candidates across the requested `levels` (omitting `levels`
+// searches all four), then ranks that superset in memory by trigram
+// Jaccard similarity against the full query, breaking ties with normalized,
+// bounded Levenshtein edit distance.
+#[allow(clippy::too_many_arguments)]
+async fn do_fuzzy_search(state: &AppState, q: &str, limit: usize, lat: Option<f64>, lng: Option<f64>, format: OutputFormat, min_score: f64, levels: Option<&str>) -> axum::response::Response {
+    let limit = if limit == 0 { 10 } else { limit.min(50) };
+    let query_lower = q.to_lowercase();
+    let query_trimmed = query_lower.trim();
+    if query_trimmed.is_empty() {
+        return render_locations(Vec::new(), format).into_response();
+    }
+
+    let levels = selected_levels(levels);
+    if levels.is_empty() {
+        return render_locations(Vec::new(), format).into_response();
+    }
+
+    let token_trigrams: Vec<Vec<String>> = query_trimmed
+        .split_whitespace()
+        .map(|token| fuzzy::trigrams(token).into_iter().collect())
+        .collect();
+
+    let mut query_parts = Vec::new();
+    let mut binds = Vec::new();
+    for level in &levels {
+        let columns = name_columns_for_level(level);
+        let (where_clause, level_binds) = fuzzy_level_where(columns, &token_trigrams);
+        query_parts.push(format!("({})", admin_level_select(level, &where_clause, FUZZY_LEVEL_ROW_CAP)));
+        binds.extend(level_binds);
+    }
+
+    let query_str = query_parts.join(" UNION ALL ");
+
+    let mut query_builder = sqlx::query(&query_str);
+    for bind in &binds {
+        query_builder = query_builder.bind(bind);
+    }
 
-            let user_loc = match (params.lat, params.lng) {
+    match query_builder.fetch_all(&state.db).await {
+        Ok(rows) => {
+            let user_loc = match (lat, lng) {
                 (Some(lat), Some(lng)) => Some(Point::new(lng, lat)),
                 _ => None,
             };
 
+            let query_trigrams = fuzzy::trigrams(query_trimmed);
+
+            let mut ranked: Vec<(f64, f64, LocationInfo)> = Vec::new();
+
             for row in rows {
-                let v_name: String = row.get("v_name");
-                let d_name: String = row.get("d_name");
-                let r_name: String = row.get("r_name");
-                let p_name: String = row.get("p_name");
-                let centroid_lat: f64 = row.get("lat");
-                let centroid_lng: f64 = row.get("lng");
+                let level: String = row.get("level");
+                let id: String = row.get("id");
+                let name: String = row.get("name");
+                let parent1: Option<String> = row.get("parent1");
+                let parent2: Option<String> = row.get("parent2");
+                let parent3: Option<String> = row.get("parent3");
+
+                let (centroid_lat, centroid_lng, bounding_box) = if level == "village" {
+                    (
+                        row.get("lat"),
+                        row.get("lng"),
+                        Some(BoundingBox {
+                            min_lat: row.get("min_lat"),
+                            min_lng: row.get("min_lng"),
+                            max_lat: row.get("max_lat"),
+                            max_lng: row.get("max_lng"),
+                        }),
+                    )
+                } else {
+                    resolve_bounds(&state.admin_bounds, &level, &id)
+                };
+
+                let location_detail = location_detail_for_level(&level, &name, &parent1, &parent2, &parent3);
+                let full_name = full_name_for_level(&state.config, &level, &location_detail);
+                let full_name_lower = full_name.to_lowercase();
+                let name_lower = name.to_lowercase();
+
+                let name_score = fuzzy::trigram_similarity(&query_trigrams, &fuzzy::trigrams(&name_lower));
+                let full_score = fuzzy::trigram_similarity(&query_trigrams, &fuzzy::trigrams(&full_name_lower));
+                let score = name_score.max(full_score);
+
+                if score < min_score {
+                    continue;
+                }
 
-                let full_name = format!("Kelurahan {}, Kecamatan {}, {}, {}", v_name, d_name, r_name, p_name);
+                // Always diffed against the entity's own `name`, not
+                // `full_name` -- `full_name` is long enough that its distance
+                // from `query` routinely exceeds `FUZZY_MAX_EDIT_DISTANCE`,
+                // which would make this tiebreak a no-op for those rows.
+                let edit_distance = fuzzy::normalized_distance(query_trimmed, &name_lower, FUZZY_MAX_EDIT_DISTANCE);
 
                 let mut dist_meters = None;
                 if let Some(user_pt) = user_loc {
@@ -309,39 +898,28 @@ async fn search_places(
                     dist_meters = Some(user_pt.haversine_distance(&loc_pt).round() as i64);
                 }
 
-                results.push(LocationInfo {
-                    level: row.get("level"),
-                    id: row.get("id"),
-                    name: v_name.clone(),
-                    location_detail: LocationDetail {
-                        province: p_name,
-                        regency: r_name,
-                        district: d_name,
-                        village: v_name,
-                    },
+                ranked.push((score, edit_distance, LocationInfo {
+                    level,
+                    id,
+                    name,
+                    location_detail,
                     full_name,
                     lat: centroid_lat,
                     lng: centroid_lng,
                     distance_meters: dist_meters,
-                });
+                    bounding_box,
+                }));
             }
 
-            if user_loc.is_some() {
-                results.sort_by(|a, b| {
-                    a.distance_meters.unwrap_or(i64::MAX)
-                     .cmp(&b.distance_meters.unwrap_or(i64::MAX))
-                });
-            } else {
-                results.sort_by(|a, b| a.name.len().cmp(&b.name.len()));
-            }
+            ranked.sort_by(|(score_a, edit_a, loc_a), (score_b, edit_b, loc_b)| {
+                score_b.partial_cmp(score_a).unwrap()
+                    .then(edit_a.partial_cmp(edit_b).unwrap())
+                    .then_with(|| loc_a.distance_meters.unwrap_or(i64::MAX).cmp(&loc_b.distance_meters.unwrap_or(i64::MAX)))
+            });
 
-            results.truncate(limit);
+            let results: Vec<LocationInfo> = ranked.into_iter().take(limit).map(|(_, _, location)| location).collect();
 
-            Json(APIResponse {
-                success: true,
-                data: Some(results),
-                error: None,
-            }).into_response()
+            render_locations(results, format).into_response()
         },
         Err(e) => {
             tracing::error!("Database error: {}", e);
@@ -356,3 +934,19 @@ async fn search_places(
         }
     }
 }
+
+async fn search_places(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SearchQuery>,
+) -> impl IntoResponse {
+    do_search(&state, &params.q, params.limit, params.lat, params.lng, params.format, params.fuzzy, params.min_score, params.levels.as_deref()).await
+}
+
+// `GET /api/v1/places/search/:query` - curl/shell-friendly alternative to
+// the query-string form, always returning the default JSON envelope.
+async fn search_places_path(
+    State(state): State<Arc<AppState>>,
+    AxumPath(query): AxumPath<String>,
+) -> impl IntoResponse {
+    do_search(&state, &query, 0, None, None, OutputFormat::Json, false, default_min_score(), None).await
+}
@@ -0,0 +1,46 @@
+use rstar::{RTree, RTreeObject, AABB};
+use sqlx::{Pool, Row, Sqlite};
+
+/// A village's bounding box, indexed by `rstar` so reverse-geocode lookups
+/// can shrink "which villages might contain this point?" to a handful of
+/// candidates without scanning every row in `villages`.
+pub struct VillageEnvelope {
+    pub id: String,
+    min_lat: f64,
+    min_lng: f64,
+    max_lat: f64,
+    max_lng: f64,
+}
+
+impl RTreeObject for VillageEnvelope {
+    type Envelope = AABB<[f64; 2]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        AABB::from_corners([self.min_lng, self.min_lat], [self.max_lng, self.max_lat])
+    }
+}
+
+pub type VillageIndex = RTree<VillageEnvelope>;
+
+/// Builds the R-tree once at startup from the `villages` table's stored
+/// bounding boxes.
+pub async fn build_village_index(db: &Pool<Sqlite>) -> Result<VillageIndex, sqlx::Error> {
+    let rows = sqlx::query("SELECT id, min_lat, min_lng, max_lat, max_lng FROM villages")
+        .fetch_all(db)
+        .await?;
+
+    let envelopes = rows.into_iter().map(|row| VillageEnvelope {
+        id: row.get("id"),
+        min_lat: row.get("min_lat"),
+        min_lng: row.get("min_lng"),
+        max_lat: row.get("max_lat"),
+        max_lng: row.get("max_lng"),
+    }).collect();
+
+    Ok(RTree::bulk_load(envelopes))
+}
+
+/// Returns the ids of every village whose bounding box contains `(lat, lng)`.
+pub fn candidates_containing(index: &VillageIndex, lat: f64, lng: f64) -> Vec<String> {
+    index.locate_all_at_point(&[lng, lat]).map(|v| v.id.clone()).collect()
+}